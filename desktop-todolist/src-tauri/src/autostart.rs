@@ -1,6 +1,15 @@
 //! Windows 开机启动：通过 HKCU\...\Run 注册表添加/移除启动项。
 //! 仅编译于 Windows；非 Windows 由 lib 层返回「仅支持 Windows」。
 
+/// 开机自启动时追加到 Run 项的命令行参数：应用据此判断自己是被自启动拉起的，
+/// 而不是用户手动双击，从而决定以隐藏到托盘的方式启动。
+pub const AUTOSTART_FLAG: &str = "--autostart";
+
+/// 当前进程的命令行参数中是否带有自启动标记。
+pub fn launched_via_autostart() -> bool {
+    std::env::args().any(|a| a == AUTOSTART_FLAG)
+}
+
 #[cfg(windows)]
 /// 规范化路径字符串，去除首尾引号和空白，用于比较
 fn normalize_path(path: &str) -> String {
@@ -11,6 +20,23 @@ fn normalize_path(path: &str) -> String {
         .to_string()
 }
 
+#[cfg(windows)]
+/// 把 Run 项的值拆成「exe 路径」与「命令行参数」两部分。
+/// exe 路径含空格时会被引号包裹，参数紧跟其后（如 `"<exe>" --autostart`）；
+/// 不含空格时整个值以空白分隔，第一段是路径，其余是参数。
+fn split_exe_and_flags(value: &str) -> (&str, &str) {
+    let trimmed = value.trim();
+    if let Some(rest) = trimmed.strip_prefix('"') {
+        if let Some(end) = rest.find('"') {
+            return (&trimmed[..end + 2], rest[end + 1..].trim());
+        }
+    }
+    match trimmed.find(' ') {
+        Some(idx) => (&trimmed[..idx], trimmed[idx + 1..].trim()),
+        None => (trimmed, ""),
+    }
+}
+
 /// 启用或禁用开机启动（仅 Windows 有效）。
 /// - enabled == true：将当前 exe 路径写入 HKCU\...\Run。
 /// - enabled == false：删除 Run 下对应项。
@@ -35,11 +61,12 @@ pub fn set_autostart_impl(enabled: bool) -> Result<(), String> {
     let exe_str = exe_path
         .to_str()
         .ok_or_else(|| "exe 路径含非法字符".to_string())?;
-    // 路径含空格时用引号包裹，符合 Windows Run 项惯例
+    // 路径含空格时用引号包裹，符合 Windows Run 项惯例；再追加 --autostart，
+    // 这样自启动拉起时应用能识别出自己不是被用户手动双击的
     let value = if exe_str.contains(' ') {
-        format!("\"{}\"", exe_str)
+        format!("\"{}\" {}", exe_str, AUTOSTART_FLAG)
     } else {
-        exe_str.to_string()
+        format!("{} {}", exe_str, AUTOSTART_FLAG)
     };
 
     let hkcu = RegKey::predef(winreg::enums::HKEY_CURRENT_USER);
@@ -135,11 +162,13 @@ pub fn is_autostart_enabled_impl() -> Result<bool, String> {
     let current: String = run_key
         .get_value(RUN_VALUE_NAME)
         .unwrap_or_default();
-    
-    // 规范化比较：去除引号和空白后比较
-    let normalized_current = normalize_path(&current);
+
+    // 只比较 exe 路径本身，忽略 --autostart 等追加的命令行参数，
+    // 否则正确注册的启动项会因为多了一段参数而被误判为「未启用」
+    let (current_exe, _flags) = split_exe_and_flags(&current);
+    let normalized_current = normalize_path(current_exe);
     let normalized_exe = normalize_path(exe_str);
-    
+
     Ok(!normalized_current.is_empty() && normalized_current == normalized_exe)
 }
 
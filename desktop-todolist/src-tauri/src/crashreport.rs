@@ -0,0 +1,177 @@
+//! 崩溃报告子系统：在应用 panic 时，尽力将可诊断信息落盘为 JSON，
+//! 而不是让应用静默退出。文件写在 `app_data_dir()/crashes/<uuid>.json`。
+//!
+//! 设计参考 Mozilla Windows runtime-exception 模块的思路：安装一个
+//! `std::panic::set_hook`，hook 内部只做「尽力而为」的 IO（任何失败都吞掉，
+//! 绝不能在 hook 里再次 panic）。
+
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::storage;
+
+/// `app_data_dir()` 下存放崩溃报告的子目录名。
+pub const CRASHES_DIRNAME: &str = "crashes";
+
+/// 单份崩溃报告：panic 信息 + 崩溃时的应用快照。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    /// Unix 时间戳（秒）
+    pub timestamp: u64,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub app_version: String,
+    /// 崩溃时的窗口配置快照（若可读取）
+    pub window_config: Option<storage::WindowConfig>,
+    /// 崩溃时已加载的待办数量
+    pub todos_count: usize,
+}
+
+/// panic hook 内需要用到的 AppHandle，在 `install` 时存入。
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+/// 返回崩溃报告目录；若无法解析应用数据目录则返回 `None`（静默跳过）。
+fn crashes_dir(app: &AppHandle) -> Option<PathBuf> {
+    let dir = app.path().app_data_dir().ok()?.join(CRASHES_DIRNAME);
+    let _ = std::fs::create_dir_all(&dir);
+    Some(dir)
+}
+
+/// 安装 panic hook，应在 `run()` 的 `.setup()` 中尽早调用一次。
+///
+/// hook 本身必须是 best-effort：任何 IO 失败都用 `let _ =` 吞掉。
+/// 写报告的代码若在 hook 内再次 panic，进程会被直接中止，不会写下任何报告。
+pub fn install(app: &AppHandle) {
+    let _ = APP_HANDLE.set(app.clone());
+
+    std::panic::set_hook(Box::new(|info| {
+        write_crash_report(info);
+    }));
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    if let Some(s) = info.payload().downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = info.payload().downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "未知 panic".to_string()
+    }
+}
+
+fn write_crash_report(info: &PanicHookInfo) {
+    let Some(app) = APP_HANDLE.get() else {
+        return;
+    };
+    let Some(dir) = crashes_dir(app) else {
+        return;
+    };
+
+    let id = uuid::Uuid::new_v4();
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let location = info
+        .location()
+        .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+    let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+    let app_version = app.package_info().version.to_string();
+    let window_config = storage::load_window_config(app.clone()).ok();
+    let todos_count = storage::load_todos(app.clone()).map(|t| t.len()).unwrap_or(0);
+
+    let report = CrashReport {
+        id: id.to_string(),
+        timestamp,
+        message: panic_message(info),
+        location,
+        backtrace,
+        app_version,
+        window_config,
+        todos_count,
+    };
+
+    if let Ok(json) = serde_json::to_string_pretty(&report) {
+        let _ = std::fs::write(dir.join(format!("{}.json", id)), json);
+    }
+}
+
+/// 记录一次非 panic 的诊断事件（如存储层从 `.bak` 备份恢复），复用崩溃报告
+/// 相同的存储格式与 `load_crash_reports` 查看入口，方便前端统一展示。
+/// 与 panic hook 一样是 best-effort：写失败静默忽略。
+///
+/// 故意不在这里调用 `storage::load_todos` / `load_window_config` 做快照——
+/// 这个函数正是被它们在「主文件损坏、刚从 .bak 恢复」的路径上调用的，
+/// 反过来再读一遍会递归回同一段恢复逻辑。
+pub fn log_recovery_event(app: &AppHandle, message: String) {
+    let Some(dir) = crashes_dir(app) else {
+        return;
+    };
+
+    let id = uuid::Uuid::new_v4();
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let report = CrashReport {
+        id: id.to_string(),
+        timestamp,
+        message,
+        location: None,
+        backtrace: String::new(),
+        app_version: app.package_info().version.to_string(),
+        window_config: None,
+        todos_count: 0,
+    };
+
+    if let Ok(json) = serde_json::to_string_pretty(&report) {
+        let _ = std::fs::write(dir.join(format!("{}.json", id)), json);
+    }
+}
+
+/// 加载本机已保存的所有崩溃报告（按时间升序），供前端判断「上次会话崩溃」
+/// 并提示用户清理 `crashes` 目录。读取失败或目录不存在时返回空列表，不报错。
+#[tauri::command]
+pub fn load_crash_reports(app: AppHandle) -> Vec<CrashReport> {
+    let Some(dir) = crashes_dir(&app) else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    let mut reports: Vec<CrashReport> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|e| std::fs::read_to_string(e.path()).ok())
+        .filter_map(|s| serde_json::from_str(&s).ok())
+        .collect();
+    reports.sort_by_key(|r| r.timestamp);
+    reports
+}
+
+/// 清空 `crashes` 目录下的所有崩溃报告，供前端在展示完「上次会话崩溃」提示后
+/// 提供「清除」操作调用。逐个删除、best-effort：单个文件删除失败不影响其余文件。
+#[tauri::command]
+pub fn clear_crash_reports(app: AppHandle) -> Result<(), String> {
+    let Some(dir) = crashes_dir(&app) else {
+        return Ok(());
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(());
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+    Ok(())
+}
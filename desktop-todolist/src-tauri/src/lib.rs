@@ -1,13 +1,17 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 
 mod autostart;
+mod crashreport;
+mod single_instance;
 mod storage;
+mod tray;
 
 use std::sync::mpsc;
 use std::time::Duration;
 
 use tauri::Manager;
 use tauri::PhysicalPosition;
+use tauri::PhysicalSize;
 use tauri::WindowEvent;
 
 #[tauri::command]
@@ -45,31 +49,39 @@ fn set_autostart(enabled: bool) -> Result<(), String> {
     autostart::set_autostart_impl(enabled)
 }
 
+/// 查询当前是否已启用开机启动，供前端渲染开关的初始状态。
+/// 供前端 invoke('get_autostart') 调用；非 Windows 恒为 false。
+#[tauri::command]
+fn get_autostart() -> Result<bool, String> {
+    autostart::is_autostart_enabled_impl()
+}
+
 /// 设置主窗口是否始终置顶，并将当前窗口位置与新的 always_on_top 写回 window.json。
-/// 供前端 invoke('set_always_on_top', { body: { enabled } }) 调用。
+/// 供前端 invoke('set_always_on_top', { body: { enabled } }) 调用；托盘菜单的
+/// 「切换置顶」也直接复用这个函数。
 #[tauri::command]
-fn set_always_on_top(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+pub(crate) fn set_always_on_top(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
     let main = app
         .get_webview_window("main")
         .ok_or_else(|| "主窗口不存在".to_string())?;
     main.set_always_on_top(enabled).map_err(|e| e.to_string())?;
 
-    // 从窗口 API 读取当前位置，与新的 always_on_top 一并写回 window.json
-    let (x, y) = main
-        .outer_position()
-        .map(|p| (p.x, p.y))
-        .unwrap_or_else(|_| {
-            // 读取失败时使用已保存的配置或默认值
-            storage::load_window_config(&app)
-                .map(|c| (c.x, c.y))
-                .unwrap_or((100, 100))
-        });
-    let config = storage::WindowConfig {
-        x,
-        y,
-        always_on_top: enabled,
-    };
-    storage::save_window_config(&app, &config)?;
+    // 用 update_window_config 把「读取已保存配置」与「写回」合并成一次加锁
+    // 的原子操作，避免与托盘命令、防抖保存线程的并发读改写互相覆盖。
+    let pos = main.outer_position().map(|p| (p.x, p.y)).ok();
+    let size = main.outer_size().map(|s| (s.width, s.height)).ok();
+    storage::update_window_config(&app, |prev| {
+        let (x, y) = pos.unwrap_or((prev.x, prev.y));
+        let (width, height) = size.unwrap_or((prev.width, prev.height));
+        storage::WindowConfig {
+            x,
+            y,
+            width,
+            height,
+            always_on_top: enabled,
+            minimize_to_tray: prev.minimize_to_tray,
+        }
+    })?;
     Ok(())
 }
 
@@ -92,10 +104,25 @@ fn is_position_valid_on_monitor(
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // 单实例检查必须在构建 Tauri 应用之前进行：第二个实例检测到已有实例
+    // 在运行后，把命令行转交过去并直接退出，不应该再弹出第二个窗口。
+    if !single_instance::try_acquire() {
+        single_instance::notify_existing_and_exit();
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet, load_todos, save_todos, start_dragging, set_autostart, set_always_on_top])
+        .invoke_handler(tauri::generate_handler![greet, load_todos, save_todos, start_dragging, set_autostart, get_autostart, set_always_on_top, crashreport::load_crash_reports, crashreport::clear_crash_reports, tray::set_minimize_to_tray])
         .setup(|app| {
+            // 尽早安装 panic hook，确保后续 setup 逻辑中的 panic 也能被捕获
+            crashreport::install(&app.handle().clone());
+
+            // 已是单实例持有者：监听后续实例的激活通知
+            single_instance::listen_for_activation(&app.handle().clone());
+
+            // 托盘图标与菜单
+            let _ = tray::setup(&app.handle().clone());
+
             // 启动时从 window.json 恢复窗口位置与置顶状态
             let config = match storage::load_window_config(app) {
                 Ok(c) => c,
@@ -107,25 +134,34 @@ pub fn run() {
                 None => return Ok(()),
             };
 
+            // 开机自启动拉起的实例直接隐藏到托盘，不在每次开机时弹出窗口
+            if autostart::launched_via_autostart() {
+                let _ = main.hide();
+            }
+
             // 置顶状态
             let _ = main.set_always_on_top(config.always_on_top);
 
-            // 位置：有效 x,y 且通过简单边界检查则设置
-            let width = 320u32;
-            let height = 400u32;
-            let valid = if let Ok(Some(mon)) = main.primary_monitor() {
-                let pos = mon.position();
-                let size = mon.size();
-                is_position_valid_on_monitor(
-                    config.x,
-                    config.y,
-                    width,
-                    height,
-                    (pos.x as i32, pos.y as i32),
-                    (size.width, size.height),
-                )
+            // 大小：恢复上次保存的窗口尺寸
+            let _ = main.set_size(PhysicalSize::new(config.width, config.height));
+
+            // 位置：只要与任意一个显示器有重叠就接受；一个都不重叠才回退到主屏居中。
+            // 多屏环境下窗口上次可能停在副屏，只查主屏会把它错误地判定为越界。
+            let valid = if let Ok(monitors) = main.available_monitors() {
+                monitors.iter().any(|mon| {
+                    let pos = mon.position();
+                    let size = mon.size();
+                    is_position_valid_on_monitor(
+                        config.x,
+                        config.y,
+                        config.width,
+                        config.height,
+                        (pos.x as i32, pos.y as i32),
+                        (size.width, size.height),
+                    )
+                })
             } else {
-                // 无法获取显示器时做数值范围检查，避免明显越界
+                // 无法获取显示器列表时做数值范围检查，避免明显越界
                 config.x >= -32768
                     && config.x <= 32767
                     && config.y >= -32768
@@ -134,15 +170,36 @@ pub fn run() {
 
             if valid {
                 let _ = main.set_position(PhysicalPosition::new(config.x as f64, config.y as f64));
+            } else if let Ok(Some(mon)) = main.primary_monitor() {
+                // 保存的位置不在任何显示器范围内：回退到主屏居中
+                let mon_pos = mon.position();
+                let mon_size = mon.size();
+                let x = mon_pos.x + (mon_size.width as i32 - config.width as i32) / 2;
+                let y = mon_pos.y + (mon_size.height as i32 - config.height as i32) / 2;
+                let _ = main.set_position(PhysicalPosition::new(x as f64, y as f64));
             }
 
-            // 监听主窗口位置变化（含拖动结束），防抖后写回 window.json
+            // 监听主窗口位置/大小变化（含拖动、缩放结束），防抖后写回 window.json；
+            // 同时拦截关闭请求，按偏好决定是最小化到托盘还是正常退出。
             let (tx, rx) = mpsc::channel();
             let app_handle = app.handle().clone();
-            main.on_window_event(move |event| {
-                if let WindowEvent::Moved(_) = event {
+            let close_app_handle = app.handle().clone();
+            main.on_window_event(move |event| match event {
+                WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
                     let _ = tx.send(());
                 }
+                WindowEvent::CloseRequested { api, .. } => {
+                    let minimize_to_tray = storage::load_window_config(close_app_handle.clone())
+                        .map(|c| c.minimize_to_tray)
+                        .unwrap_or(false);
+                    if minimize_to_tray {
+                        api.prevent_close();
+                        if let Some(w) = close_app_handle.get_webview_window("main") {
+                            let _ = w.hide();
+                        }
+                    }
+                }
+                _ => {}
             });
             std::thread::spawn(move || {
                 while rx.recv().is_ok() {
@@ -157,13 +214,23 @@ pub fn run() {
                     let _ = app_handle.run_on_main_thread(move || {
                         if let Some(m) = handle.get_webview_window("main") {
                             let (x, y) = m.outer_position().map(|p| (p.x, p.y)).unwrap_or((100, 100));
+                            let (width, height) = m
+                                .outer_size()
+                                .map(|s| (s.width, s.height))
+                                .unwrap_or((storage::DEFAULT_WIDTH, storage::DEFAULT_HEIGHT));
                             let always_on_top = m.is_always_on_top().unwrap_or(true);
-                            let config = storage::WindowConfig {
-                                x,
-                                y,
-                                always_on_top,
-                            };
-                            let _ = storage::save_window_config(&handle, &config);
+                            // 用 update_window_config 做一次加锁的原子读改写，避免与
+                            // 置顶命令、托盘命令并发时互相覆盖 minimize_to_tray 等偏好
+                            let _ = storage::update_window_config(&handle, |prev| {
+                                storage::WindowConfig {
+                                    x,
+                                    y,
+                                    width,
+                                    height,
+                                    always_on_top,
+                                    minimize_to_tray: prev.minimize_to_tray,
+                                }
+                            });
                         }
                     });
                 }
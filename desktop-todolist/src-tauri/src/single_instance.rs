@@ -0,0 +1,248 @@
+//! 单实例保证：防止应用被启动多次（例如开机自启动的 Run 项在应用已打开时
+//! 再次触发），导致两个进程争抢同一份 `window.json`。
+//!
+//! 第二个实例检测到已有实例在运行后，把自己的命令行通过本地 IPC 转交给
+//! 第一个实例，然后自行退出；第一个实例收到任意消息后前置自身窗口。
+//!
+//! Windows 下用命名互斥体判断「是否已有实例」、命名管道做 IPC；
+//! 其它平台用固定位置的独占锁文件判断、Unix Domain Socket 做 IPC。
+//! `try_acquire` 必须在构建 Tauri `App` 之前调用（此时还没有 `AppHandle`
+//! 可用于解析 `app_data_dir()`），因此锁/管道/socket 的位置由
+//! [`instance_dir`] 独立解析，不依赖 Tauri 的 PathResolver。
+
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager};
+
+/// 派生互斥体名 / 管道名 / socket 文件名的固定标识符，来自 bundle identifier。
+const APP_ID: &str = "com.todolist.desktop-todolist";
+
+/// 单实例用到的文件（锁文件 / socket）所在目录。独立于 Tauri 的
+/// `app_data_dir()` 解析，因为这一步发生在 Tauri `App` 构建之前。
+fn instance_dir() -> PathBuf {
+    std::env::temp_dir().join(APP_ID)
+}
+
+/// 尝试成为单实例的持有者。
+///
+/// 返回 `true` 表示当前进程是唯一实例，应继续正常启动；
+/// 返回 `false` 表示已有实例在运行——调用方应把命令行转交给它（见
+/// [`notify_existing_and_exit`]）然后退出，不再构建 Tauri 应用。
+pub fn try_acquire() -> bool {
+    let dir = instance_dir();
+    let _ = std::fs::create_dir_all(&dir);
+    platform::try_acquire(&dir)
+}
+
+/// 在已持有单实例锁的主实例上启动 IPC 监听：收到任意消息即前置主窗口。
+/// 应在 `run()` 的 `.setup()` 中、确认 `try_acquire()` 返回 `true` 后调用一次。
+pub fn listen_for_activation(app: &AppHandle) {
+    let app = app.clone();
+    let dir = instance_dir();
+    std::thread::spawn(move || platform::listen(&dir, &app));
+}
+
+/// 将当前进程的命令行参数转交给已运行的实例，用于唤醒/前置对方窗口。
+/// 发送失败时静默忽略——反正本进程接下来就要退出了。
+pub fn notify_existing_and_exit() -> ! {
+    let args: Vec<String> = std::env::args().collect();
+    let payload = args.join("\u{1}");
+    platform::send(&instance_dir(), &payload);
+    std::process::exit(0);
+}
+
+/// 前置并聚焦主窗口：show + unminimize + set_focus，三步都 best-effort。
+fn focus_main_window(app: &AppHandle) {
+    if let Some(main) = app.get_webview_window("main") {
+        let _ = main.show();
+        let _ = main.unminimize();
+        let _ = main.set_focus();
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::{focus_main_window, APP_ID};
+    use std::ffi::OsStr;
+    use std::io::Write;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use std::ptr;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+
+    use tauri::AppHandle;
+    use windows_sys::Win32::Foundation::{
+        CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, ERROR_PIPE_CONNECTED, HANDLE,
+        INVALID_HANDLE_VALUE,
+    };
+    use windows_sys::Win32::Storage::FileSystem::ReadFile;
+    use windows_sys::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, PIPE_ACCESS_DUPLEX, PIPE_TYPE_BYTE,
+        PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+    use windows_sys::Win32::System::Threading::CreateMutexW;
+
+    /// 互斥体句柄随进程存活，故意不关闭——进程退出时系统自动释放。
+    static MUTEX_HANDLE: OnceLock<usize> = OnceLock::new();
+
+    /// 单次读取/写入的缓冲区大小，对这里传递的「命令行拼接字符串」足够。
+    const PIPE_BUFFER_SIZE: u32 = 4096;
+
+    fn wide(s: &str) -> Vec<u16> {
+        OsStr::new(s).encode_wide().chain(std::iter::once(0)).collect()
+    }
+
+    fn mutex_name() -> Vec<u16> {
+        wide(&format!("Local\\{}-single-instance", APP_ID))
+    }
+
+    fn pipe_path() -> String {
+        format!(r"\\.\pipe\{}-ipc", APP_ID)
+    }
+
+    pub fn try_acquire(_dir: &Path) -> bool {
+        let name = mutex_name();
+        let handle: HANDLE = unsafe { CreateMutexW(ptr::null(), 0, name.as_ptr()) };
+        if handle == 0 {
+            // 创建失败（极少见）时不要因为平台细节阻塞启动
+            return true;
+        }
+        let already_running = unsafe { GetLastError() } == ERROR_ALREADY_EXISTS;
+        if already_running {
+            unsafe { CloseHandle(handle) };
+            return false;
+        }
+        let _ = MUTEX_HANDLE.set(handle as usize);
+        true
+    }
+
+    pub fn listen(_dir: &Path, app: &AppHandle) {
+        // 每一轮都重新创建一个管道实例并阻塞等待客户端连接：命名管道的一个
+        // 实例只能服务一个客户端，处理完一次激活请求后必须重开下一轮。
+        loop {
+            match create_and_accept_connection() {
+                Some(handle) => {
+                    read_all(handle);
+                    unsafe { CloseHandle(handle) };
+                    focus_main_window(app);
+                }
+                None => std::thread::sleep(Duration::from_millis(200)),
+            }
+        }
+    }
+
+    pub fn send(_dir: &Path, payload: &str) {
+        if let Ok(mut stream) = std::fs::OpenOptions::new().write(true).open(pipe_path()) {
+            let _ = stream.write_all(payload.as_bytes());
+        }
+    }
+
+    /// 用 `CreateNamedPipeW` 创建服务端管道实例，再用 `ConnectNamedPipe`
+    /// 阻塞等待客户端连接上来。这一步是 IPC 能工作的关键——第二个实例的
+    /// `send()` 用 `CreateFileW`（即 `OpenOptions::open`）去连接，但那只是
+    /// 客户端 API，必须有一个已经在监听的服务端实例它才能连上。
+    fn create_and_accept_connection() -> Option<HANDLE> {
+        let name = wide(&pipe_path());
+        let handle = unsafe {
+            CreateNamedPipeW(
+                name.as_ptr(),
+                PIPE_ACCESS_DUPLEX,
+                PIPE_TYPE_BYTE | PIPE_WAIT,
+                PIPE_UNLIMITED_INSTANCES,
+                PIPE_BUFFER_SIZE,
+                PIPE_BUFFER_SIZE,
+                0,
+                ptr::null(),
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return None;
+        }
+
+        let connected = unsafe { ConnectNamedPipe(handle, ptr::null_mut()) };
+        if connected == 0 && unsafe { GetLastError() } != ERROR_PIPE_CONNECTED {
+            unsafe { CloseHandle(handle) };
+            return None;
+        }
+        Some(handle)
+    }
+
+    fn read_all(handle: HANDLE) {
+        let mut buf = [0u8; PIPE_BUFFER_SIZE as usize];
+        loop {
+            let mut read = 0u32;
+            let ok = unsafe {
+                ReadFile(
+                    handle,
+                    buf.as_mut_ptr(),
+                    buf.len() as u32,
+                    &mut read,
+                    ptr::null_mut(),
+                )
+            };
+            if ok == 0 || read == 0 {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod platform {
+    use super::focus_main_window;
+    use std::io::{Read, Write};
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::OnceLock;
+
+    use tauri::AppHandle;
+
+    /// 锁文件本身随进程存活以保持 flock 持有；进程退出时内核自动释放。
+    static LOCK_FILE: OnceLock<std::fs::File> = OnceLock::new();
+
+    fn lock_path(dir: &Path) -> PathBuf {
+        dir.join("single-instance.lock")
+    }
+
+    fn socket_path(dir: &Path) -> PathBuf {
+        dir.join("single-instance.sock")
+    }
+
+    pub fn try_acquire(dir: &Path) -> bool {
+        let file = match std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(lock_path(dir))
+        {
+            Ok(f) => f,
+            Err(_) => return true,
+        };
+        let locked = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0;
+        if locked {
+            let _ = LOCK_FILE.set(file);
+            // 旧 socket 可能是上次异常退出留下的，既然拿到了锁就可以放心清理
+            let _ = std::fs::remove_file(socket_path(dir));
+        }
+        locked
+    }
+
+    pub fn listen(dir: &Path, app: &AppHandle) {
+        let Ok(listener) = UnixListener::bind(socket_path(dir)) else {
+            return;
+        };
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut buf = String::new();
+            let _ = stream.read_to_string(&mut buf);
+            focus_main_window(app);
+        }
+    }
+
+    pub fn send(dir: &Path, payload: &str) {
+        if let Ok(mut stream) = UnixStream::connect(socket_path(dir)) {
+            let _ = stream.write_all(payload.as_bytes());
+        }
+    }
+}
@@ -2,12 +2,15 @@
 //! 使用 Tauri 2 的 PathResolver（app.path().app_data_dir()）解析应用数据目录。
 //! 含 Todo 结构体与 load_todos 读取逻辑。
 
-use std::io::ErrorKind;
+use std::io::{ErrorKind, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager, Runtime};
 
+use crate::crashreport;
+
 /// 单条待办，与设计一致：id、文案、完成状态、排序。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Todo {
@@ -30,16 +33,28 @@ struct TodoRaw {
     order: Option<u32>,
 }
 
-/// 窗口配置：位置与置顶偏好，对应 `window.json`。
+/// 窗口默认宽度（像素），与前端无边框窗口的初始大小一致。
+pub const DEFAULT_WIDTH: u32 = 320;
+/// 窗口默认高度（像素）。
+pub const DEFAULT_HEIGHT: u32 = 400;
+
+/// 窗口配置：位置、大小与置顶偏好，对应 `window.json`。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WindowConfig {
     /// 窗口左上角 x 坐标
     pub x: i32,
     /// 窗口左上角 y 坐标
     pub y: i32,
+    /// 窗口宽度
+    pub width: u32,
+    /// 窗口高度
+    pub height: u32,
     /// 是否始终置顶
     #[serde(rename = "alwaysOnTop")]
     pub always_on_top: bool,
+    /// 关闭按钮是否最小化到系统托盘，而不是退出应用
+    #[serde(rename = "minimizeToTray", default)]
+    pub minimize_to_tray: bool,
 }
 
 /// 反序列化时允许缺字段，用 Option + default 补全。
@@ -49,8 +64,14 @@ struct WindowConfigRaw {
     x: Option<i32>,
     #[serde(default)]
     y: Option<i32>,
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
     #[serde(rename = "alwaysOnTop", default)]
     always_on_top: Option<bool>,
+    #[serde(rename = "minimizeToTray", default)]
+    minimize_to_tray: Option<bool>,
 }
 
 /// 应用数据目录下 `todos.json` 的文件名。
@@ -108,43 +129,102 @@ pub fn window_config_path<M: Manager<R>, R: Runtime>(app: &M) -> Result<PathBuf,
     Ok(window_config_path_in_dir(&dir))
 }
 
+/// 给定目标文件路径，返回同目录下追加了 `suffix` 的兄弟路径
+/// （如 `todos.json` + `.bak` -> `todos.json.bak`）。
+fn sibling_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// 原子、抗损坏地写入一个文件：先写临时文件并 `sync_all`，再把旧文件备份为
+/// `.bak`，最后 `rename` 到目标路径（同卷下是原子操作）。这样即使写入过程中
+/// 崩溃或断电，目标文件要么是写入前的旧内容，要么是完整的新内容，不会被截断。
+///
+/// 临时文件名带 UUID 后缀而不是固定的 `.tmp`：同一目标路径可能有多个线程
+/// 并发调用本函数（如 window.json 的置顶命令、托盘菜单、移动/缩放防抖线程），
+/// 固定名字会导致后一个 `File::create` 把前一个还没 rename 的临时文件截断。
+fn write_atomic_with_backup(path: &Path, contents: &str) -> std::io::Result<()> {
+    let tmp_path = sibling_with_suffix(path, &format!(".tmp.{}", uuid::Uuid::new_v4()));
+    {
+        let mut tmp = std::fs::File::create(&tmp_path)?;
+        tmp.write_all(contents.as_bytes())?;
+        tmp.flush()?;
+        tmp.sync_all()?;
+    }
+    if path.exists() {
+        let _ = std::fs::copy(path, sibling_with_suffix(path, ".bak"));
+    }
+    std::fs::rename(&tmp_path, path)
+}
+
+/// 解析 `todos.json` 的文本内容；无效 JSON 返回 `None`，有效内容（含空列表）
+/// 返回补全好缺字段的待办列表。
+fn parse_todos(contents: &str) -> Option<Vec<Todo>> {
+    let raw_list: Vec<TodoRaw> = serde_json::from_str(contents).ok()?;
+    Some(
+        raw_list
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| Todo {
+                id: r.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                text: r.text.unwrap_or_default(),
+                done: r.done.unwrap_or(false),
+                order: r.order.unwrap_or(i as u32),
+            })
+            .collect(),
+    )
+}
+
+/// 读取并解析某一份 todos 文件；文件不存在或内容无效都视为「没有可用数据」。
+fn read_todos_file(path: &Path) -> Option<Vec<Todo>> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    parse_todos(&contents)
+}
+
 /// 从应用数据目录下的 `todos.json` 加载待办列表。
 ///
 /// - 路径通过 `todos_json_path(&app)` 获取；若解析路径失败则返回 `Err`。
 /// - 文件不存在或解析失败（无效 JSON）时返回 `Ok(Vec::new())`，不 panic、不弹窗。
 /// - 对每条缺字段做兼容：缺 `id` 则生成 UUID，缺 `text` 用 `""`，缺 `done` 用 `false`，缺 `order` 用下标。
+/// - 主文件缺失或解析失败（无效 JSON）时，若 `.bak` 备份存在且非空，则从备份恢复
+///   （对应断电/崩溃导致主文件被截断的情况），并记录一条崩溃报告诊断事件。
+///   注意：主文件能成功解析出一个*空*列表（如用户清空了所有待办后保存）不算
+///   「损坏」，不会触发回退——否则一次合法的「清空」在重启后会被静默撤销。
 pub fn load_todos(app: AppHandle) -> Result<Vec<Todo>, String> {
     let path = todos_json_path(&app).map_err(|e| e.to_string())?;
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
-        Err(e) => return Err(e.to_string()),
-    };
-    let raw_list: Vec<TodoRaw> = match serde_json::from_str(&contents) {
-        Ok(v) => v,
-        Err(_) => return Ok(Vec::new()),
-    };
-    let todos = raw_list
-        .into_iter()
-        .enumerate()
-        .map(|(i, r)| Todo {
-            id: r.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
-            text: r.text.unwrap_or_default(),
-            done: r.done.unwrap_or(false),
-            order: r.order.unwrap_or(i as u32),
-        })
-        .collect();
-    Ok(todos)
+    let primary = read_todos_file(&path);
+    if let Some(todos) = primary {
+        return Ok(todos);
+    }
+
+    if let Some(backup) = read_todos_file(&sibling_with_suffix(&path, ".bak")) {
+        if !backup.is_empty() {
+            crashreport::log_recovery_event(
+                &app,
+                format!(
+                    "{} 缺失/损坏，已从 .bak 备份恢复 {} 条待办",
+                    TODOS_FILENAME,
+                    backup.len()
+                ),
+            );
+            return Ok(backup);
+        }
+    }
+
+    Ok(Vec::new())
 }
 
 /// 将完整待办列表写入应用数据目录下的 `todos.json`。
 ///
 /// - 路径通过 `todos_json_path(&app)` 获取（该函数保证目录存在）。
+/// - 原子写入：先写临时文件并 `sync_all`，备份旧文件为 `.bak`，再 `rename` 到目标
+///   路径，避免写入过程中崩溃/断电截断文件（见 [`write_atomic_with_backup`]）。
 /// - 写失败时返回 `Err(String)`，供前端提示「保存失败，请重试」。
 pub fn save_todos<M: Manager<R>, R: Runtime>(app: &M, todos: &[Todo]) -> Result<(), String> {
     let path = todos_json_path(app).map_err(|e| e.to_string())?;
     let json = serde_json::to_string_pretty(todos).map_err(|e| e.to_string())?;
-    std::fs::write(&path, json).map_err(|e| e.to_string())?;
+    write_atomic_with_backup(&path, &json).map_err(|e| e.to_string())?;
     Ok(())
 }
 
@@ -153,42 +233,104 @@ fn default_window_config() -> WindowConfig {
     WindowConfig {
         x: 100,
         y: 100,
+        width: DEFAULT_WIDTH,
+        height: DEFAULT_HEIGHT,
         always_on_top: true,
+        minimize_to_tray: false,
+    }
+}
+
+/// 把缺字段补全后的 `WindowConfigRaw` 转成 `WindowConfig`。
+fn raw_to_window_config(raw: WindowConfigRaw) -> WindowConfig {
+    WindowConfig {
+        x: raw.x.unwrap_or(100),
+        y: raw.y.unwrap_or(100),
+        width: raw.width.unwrap_or(DEFAULT_WIDTH),
+        height: raw.height.unwrap_or(DEFAULT_HEIGHT),
+        always_on_top: raw.always_on_top.unwrap_or(true),
+        minimize_to_tray: raw.minimize_to_tray.unwrap_or(false),
+    }
+}
+
+/// 串行化所有对 `window.json` 的读写。这份配置有多个写者——置顶命令、
+/// 托盘的「最小化到托盘」命令、窗口移动/缩放防抖保存。没有这把锁的话，
+/// 先读后写的「切换置顶」之类操作会互相覆盖、丢失其中一次更新。所有读写都应通过本模块的 `load_window_config` /
+/// `save_window_config` / `update_window_config` 进行，不要绕过去直接读写文件。
+static WINDOW_CONFIG_LOCK: Mutex<()> = Mutex::new(());
+
+fn load_window_config_locked(app: &AppHandle) -> Result<WindowConfig, String> {
+    let path = window_config_path(app).map_err(|e| e.to_string())?;
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(c) => Some(c),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(e.to_string()),
+    };
+    let raw = contents.and_then(|c| serde_json::from_str::<WindowConfigRaw>(&c).ok());
+    if let Some(raw) = raw {
+        return Ok(raw_to_window_config(raw));
+    }
+
+    if let Ok(backup_contents) = std::fs::read_to_string(sibling_with_suffix(&path, ".bak")) {
+        if let Ok(raw) = serde_json::from_str::<WindowConfigRaw>(&backup_contents) {
+            crashreport::log_recovery_event(
+                &app,
+                format!("{} 缺失/损坏，已从 .bak 备份恢复窗口配置", WINDOW_FILENAME),
+            );
+            return Ok(raw_to_window_config(raw));
+        }
     }
+
+    Ok(default_window_config())
+}
+
+fn save_window_config_locked<M: Manager<R>, R: Runtime>(
+    app: &M,
+    config: &WindowConfig,
+) -> Result<(), String> {
+    let path = window_config_path(app).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    write_atomic_with_backup(&path, &json).map_err(|e| e.to_string())?;
+    Ok(())
 }
 
 /// 从应用数据目录下的 `window.json` 加载窗口配置。
 ///
 /// - 路径通过 `window_config_path(&app)` 获取；若解析路径失败则返回 `Err`。
-/// - 文件不存在或解析失败（无效 JSON）时返回默认值（x: 100, y: 100, always_on_top: true）。
+/// - 文件不存在或解析失败（无效 JSON）时，若 `.bak` 备份存在且能解析，则从备份恢复
+///   （对应断电/崩溃导致主文件被截断的情况），并记录一条崩溃报告诊断事件；
+///   否则返回默认值（x: 100, y: 100, always_on_top: true）。
 /// - 缺字段时用默认值补全。
+/// - 与其它 `window.json` 读写互斥，见 [`WINDOW_CONFIG_LOCK`]。
 pub fn load_window_config(app: AppHandle) -> Result<WindowConfig, String> {
-    let path = window_config_path(&app).map_err(|e| e.to_string())?;
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(default_window_config()),
-        Err(e) => return Err(e.to_string()),
-    };
-    let raw: WindowConfigRaw = match serde_json::from_str(&contents) {
-        Ok(r) => r,
-        Err(_) => return Ok(default_window_config()),
-    };
-    Ok(WindowConfig {
-        x: raw.x.unwrap_or(100),
-        y: raw.y.unwrap_or(100),
-        always_on_top: raw.always_on_top.unwrap_or(true),
-    })
+    let _guard = WINDOW_CONFIG_LOCK.lock().unwrap();
+    load_window_config_locked(&app)
 }
 
 /// 将窗口配置写入应用数据目录下的 `window.json`。
 ///
 /// - 路径通过 `window_config_path(&app)` 获取（该函数保证目录存在）。
-/// - 供后续 Task 7/8/10 在窗口移动或置顶切换时调用。
+/// - 原子写入：先写临时文件并 `sync_all`，备份旧文件为 `.bak`，再 `rename` 到目标
+///   路径，避免写入过程中崩溃/断电截断文件（见 [`write_atomic_with_backup`]）。
+/// - 与其它 `window.json` 读写互斥，见 [`WINDOW_CONFIG_LOCK`]。若需要「先读后改
+///   再写」，用 [`update_window_config`] 而不是分别调用 `load`/`save`，
+///   否则两次调用之间可能被别的写者插入，丢失其中一次更新。
 pub fn save_window_config<M: Manager<R>, R: Runtime>(app: &M, config: &WindowConfig) -> Result<(), String> {
-    let path = window_config_path(app).map_err(|e| e.to_string())?;
-    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
-    std::fs::write(&path, json).map_err(|e| e.to_string())?;
-    Ok(())
+    let _guard = WINDOW_CONFIG_LOCK.lock().unwrap();
+    save_window_config_locked(app, config)
+}
+
+/// 原子地「读取 -> 修改 -> 写回」窗口配置：整个过程持有 [`WINDOW_CONFIG_LOCK`]，
+/// 不会被其它并发的 `load_window_config`/`save_window_config`/`update_window_config`
+/// 调用插入，避免置顶命令、托盘命令等各自读改写导致的丢失更新。
+pub fn update_window_config(
+    app: &AppHandle,
+    mutate: impl FnOnce(WindowConfig) -> WindowConfig,
+) -> Result<WindowConfig, String> {
+    let _guard = WINDOW_CONFIG_LOCK.lock().unwrap();
+    let current = load_window_config_locked(app)?;
+    let updated = mutate(current);
+    save_window_config_locked(app, &updated)?;
+    Ok(updated)
 }
 
 #[cfg(test)]
@@ -218,4 +360,42 @@ mod tests {
         );
         assert_eq!(path.file_name().unwrap(), WINDOW_FILENAME);
     }
+
+    #[test]
+    fn sibling_with_suffix_appends_to_file_name() {
+        let path = Path::new("/tmp/app-data/todos.json");
+        assert_eq!(
+            sibling_with_suffix(path, ".bak"),
+            Path::new("/tmp/app-data/todos.json.bak")
+        );
+        assert_eq!(
+            sibling_with_suffix(path, ".tmp"),
+            Path::new("/tmp/app-data/todos.json.tmp")
+        );
+    }
+
+    #[test]
+    fn write_atomic_with_backup_round_trips_and_keeps_previous_as_bak() {
+        let dir = std::env::temp_dir().join("test-todolist-storage-atomic");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("todos.json");
+        let bak_path = sibling_with_suffix(&path, ".bak");
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&bak_path);
+
+        write_atomic_with_backup(&path, "[]").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[]");
+        assert!(!bak_path.exists(), "首次写入不存在旧文件，不应产生 .bak");
+
+        write_atomic_with_backup(&path, "[1]").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[1]");
+        assert_eq!(
+            std::fs::read_to_string(&bak_path).unwrap(),
+            "[]",
+            ".bak 应保留写入前的旧内容"
+        );
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&bak_path);
+    }
 }
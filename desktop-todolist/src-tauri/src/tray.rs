@@ -0,0 +1,102 @@
+//! 系统托盘：这是一个常驻后台的小巧待办 widget，比起占用任务栏位置，
+//! 更适合常驻在通知区域。提供最小化到托盘、托盘菜单快速操作。
+//!
+//! 左键单击托盘图标切换主窗口显示/隐藏；菜单提供显示/隐藏、快速添加待办、
+//! 切换置顶（复用 [`crate::set_always_on_top`]）、退出。
+
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::storage;
+
+const MENU_ID_SHOW_HIDE: &str = "tray-show-hide";
+const MENU_ID_ADD_TODO: &str = "tray-add-todo";
+const MENU_ID_ALWAYS_ON_TOP: &str = "tray-always-on-top";
+const MENU_ID_QUIT: &str = "tray-quit";
+
+/// 托盘菜单「添加待办…」被点击时发给前端的事件名，前端收到后应聚焦输入框。
+pub const EVENT_ADD_TODO: &str = "tray://add-todo";
+
+/// 创建托盘图标与菜单，应在 `run()` 的 `.setup()` 中调用一次。
+/// 拿不到默认窗口图标时放弃创建托盘（不影响主窗口正常使用）。
+pub fn setup(app: &AppHandle) -> tauri::Result<()> {
+    let Some(icon) = app.default_window_icon().cloned() else {
+        return Ok(());
+    };
+
+    let show_hide = MenuItem::with_id(app, MENU_ID_SHOW_HIDE, "显示/隐藏", true, None::<&str>)?;
+    let add_todo = MenuItem::with_id(app, MENU_ID_ADD_TODO, "添加待办…", true, None::<&str>)?;
+    let always_on_top = MenuItem::with_id(app, MENU_ID_ALWAYS_ON_TOP, "切换置顶", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, MENU_ID_QUIT, "退出", true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let menu = Menu::with_items(app, &[&show_hide, &add_todo, &always_on_top, &separator, &quit])?;
+
+    TrayIconBuilder::new()
+        .icon(icon)
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| match event.id.as_ref() {
+            MENU_ID_SHOW_HIDE => toggle_visibility(app),
+            MENU_ID_ADD_TODO => {
+                show_and_focus(app);
+                let _ = app.emit(EVENT_ADD_TODO, ());
+            }
+            MENU_ID_ALWAYS_ON_TOP => toggle_always_on_top(app),
+            MENU_ID_QUIT => app.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                toggle_visibility(tray.app_handle());
+            }
+        })
+        .build(app)?;
+
+    Ok(())
+}
+
+fn toggle_visibility(app: &AppHandle) {
+    let Some(main) = app.get_webview_window("main") else {
+        return;
+    };
+    if main.is_visible().unwrap_or(true) {
+        let _ = main.hide();
+    } else {
+        let _ = main.show();
+        let _ = main.set_focus();
+    }
+}
+
+fn show_and_focus(app: &AppHandle) {
+    if let Some(main) = app.get_webview_window("main") {
+        let _ = main.show();
+        let _ = main.set_focus();
+    }
+}
+
+fn toggle_always_on_top(app: &AppHandle) {
+    let Some(main) = app.get_webview_window("main") else {
+        return;
+    };
+    let current = main.is_always_on_top().unwrap_or(false);
+    let _ = crate::set_always_on_top(app.clone(), !current);
+}
+
+/// 持久化「关闭按钮是否最小化到托盘」偏好。
+/// 供前端 invoke('set_minimize_to_tray', { body: { enabled } }) 调用。
+#[tauri::command]
+pub fn set_minimize_to_tray(app: AppHandle, enabled: bool) -> Result<(), String> {
+    // 用 update_window_config 做一次加锁的原子读改写，避免与置顶命令、防抖
+    // 保存线程并发时互相覆盖对方刚写入的字段
+    storage::update_window_config(&app, |mut config| {
+        config.minimize_to_tray = enabled;
+        config
+    })?;
+    Ok(())
+}